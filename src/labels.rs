@@ -0,0 +1,242 @@
+use gl;
+use std;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+
+/// A single glyph's location within the font atlas texture, in pixels,
+/// plus the metrics needed to lay out a line of text.
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub origin_x: f32,
+    pub origin_y: f32,
+    pub advance: f32,
+}
+
+pub struct FontAtlas {
+    pub texture: u32,
+    pub atlas_width: f32,
+    pub atlas_height: f32,
+    pub characters: HashMap<char, Glyph>,
+}
+
+/// A billboarded text label: a small VBO of textured quads (two triangles
+/// per glyph) anchored at a world-space position.
+pub struct Label {
+    vbo: u32,
+    vertex_count: i32,
+    pub world_position: (f32, f32, f32),
+}
+
+impl Label {
+    pub fn vbo(&self) -> u32 {
+        self.vbo
+    }
+
+    pub fn vertex_count(&self) -> i32 {
+        self.vertex_count
+    }
+}
+
+/// Reads a manifest describing a font atlas PNG: `width`/`height` of the
+/// texture in pixels, and a `characters` map from glyph to its pixel rect
+/// and metrics, e.g.
+///
+/// ```json
+/// {
+///   "width": 512, "height": 512,
+///   "characters": {
+///     "A": {"x":0,"y":0,"width":14,"height":18,"originX":0,"originY":18,"advance":15}
+///   }
+/// }
+/// ```
+/// Returns `None` if either the manifest or the texture can't be opened or
+/// read, so a missing font atlas just skips labels rather than crashing.
+pub fn load_font_atlas(manifest_path: &str, texture_rgba_path: &str) -> Option<FontAtlas> {
+    let contents = match File::open(manifest_path).and_then(|mut f| {
+        let mut s = String::new();
+        f.read_to_string(&mut s).map(|_| s)
+    }) {
+        Ok(s) => s,
+        Err(_) => return None,
+    };
+
+    let (atlas_width, atlas_height, characters) = parse_manifest(&contents);
+
+    let mut texture_data = Vec::new();
+    if File::open(texture_rgba_path)
+        .and_then(|mut f| f.read_to_end(&mut texture_data))
+        .is_err()
+    {
+        return None;
+    }
+
+    let texture = unsafe {
+        let mut tex = 0;
+        gl::GenTextures(1, &mut tex);
+        gl::BindTexture(gl::TEXTURE_2D, tex);
+        gl::TexImage2D(
+            gl::TEXTURE_2D, 0, gl::RGBA as i32, atlas_width as i32, atlas_height as i32, 0,
+            gl::RGBA, gl::UNSIGNED_BYTE, std::mem::transmute(&texture_data[0]));
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        tex
+    };
+
+    Some(FontAtlas { texture, atlas_width, atlas_height, characters })
+}
+
+/// Hand-rolled parser for the small, fixed-shape manifest above; a full
+/// JSON library would be overkill for a handful of numeric fields per glyph.
+fn parse_manifest(json: &str) -> (f32, f32, HashMap<char, Glyph>) {
+    let width = find_number_field(json, "\"width\"").unwrap_or(0.0);
+    let height = find_number_field(json, "\"height\"").unwrap_or(0.0);
+
+    let mut characters = HashMap::new();
+    let chars_start = json.find("\"characters\"").expect("manifest missing \"characters\" map");
+    let body = &json[chars_start..];
+
+    let mut rest = body;
+    while let Some(key_start) = rest.find('"').map(|i| i + 1) {
+        let after_first_quote = &rest[key_start..];
+        let key_end = match after_first_quote.find('"') {
+            Some(i) => i,
+            None => break,
+        };
+        let key = &after_first_quote[..key_end];
+        if key == "characters" {
+            rest = &after_first_quote[key_end + 1..];
+            continue;
+        }
+        if key.chars().count() != 1 {
+            // not a glyph entry (or we've run past the map); stop scanning
+            break;
+        }
+        let glyph_ch = key.chars().next().unwrap();
+
+        let obj_start = after_first_quote[key_end..].find('{').map(|i| key_end + i);
+        let obj_end = after_first_quote[key_end..].find('}').map(|i| key_end + i);
+        let (obj_start, obj_end) = match (obj_start, obj_end) {
+            (Some(s), Some(e)) if e > s => (s, e),
+            _ => break,
+        };
+        let obj = &after_first_quote[obj_start..obj_end];
+
+        characters.insert(glyph_ch, Glyph {
+            x: find_number_field(obj, "\"x\"").unwrap_or(0.0),
+            y: find_number_field(obj, "\"y\"").unwrap_or(0.0),
+            width: find_number_field(obj, "\"width\"").unwrap_or(0.0),
+            height: find_number_field(obj, "\"height\"").unwrap_or(0.0),
+            origin_x: find_number_field(obj, "\"originX\"").unwrap_or(0.0),
+            origin_y: find_number_field(obj, "\"originY\"").unwrap_or(0.0),
+            advance: find_number_field(obj, "\"advance\"").unwrap_or(0.0),
+        });
+
+        rest = &after_first_quote[obj_end + 1..];
+    }
+
+    (width, height, characters)
+}
+
+fn find_number_field(json: &str, key: &str) -> Option<f32> {
+    let key_pos = json.find(key)?;
+    let after_key = &json[key_pos + key.len()..];
+    let colon_pos = after_key.find(':')?;
+    let after_colon = after_key[colon_pos + 1..].trim_start();
+
+    let end = after_colon.find(|c: char| c == ',' || c == '}' || c.is_whitespace())
+        .unwrap_or(after_colon.len());
+    after_colon[..end].parse::<f32>().ok()
+}
+
+/// Builds a billboarded label at `world_position` by laying out `text`
+/// against `atlas`, emitting one textured quad per glyph in local
+/// (screen-facing) space: interleaved `x, y, u, v` per vertex, two
+/// triangles per glyph. The quads are offset in clip space (scaled by `w`)
+/// at draw time so the label always faces the viewer at a constant
+/// screen size, independent of its depth or the active projection.
+pub unsafe fn build_label(atlas: &FontAtlas, text: &str, world_position: (f32, f32, f32), pixel_scale: f32) -> Label {
+    let mut vertex_data: Vec<f32> = Vec::new();
+    let mut cursor_x = 0.0f32;
+
+    for ch in text.chars() {
+        let glyph = match atlas.characters.get(&ch) {
+            Some(g) => g,
+            None => continue,
+        };
+
+        let x0 = (cursor_x - glyph.origin_x) * pixel_scale;
+        let x1 = (cursor_x - glyph.origin_x + glyph.width) * pixel_scale;
+        let y0 = (glyph.origin_y - glyph.height) * pixel_scale;
+        let y1 = glyph.origin_y * pixel_scale;
+
+        let u0 = glyph.x / atlas.atlas_width;
+        let u1 = (glyph.x + glyph.width) / atlas.atlas_width;
+        let v0 = (glyph.y + glyph.height) / atlas.atlas_height;
+        let v1 = glyph.y / atlas.atlas_height;
+
+        let quad: [[f32; 4]; 6] = [
+            [x0, y0, u0, v0], [x1, y0, u1, v0], [x1, y1, u1, v1],
+            [x0, y0, u0, v0], [x1, y1, u1, v1], [x0, y1, u0, v1],
+        ];
+        for vertex in quad.iter() {
+            vertex_data.extend_from_slice(vertex);
+        }
+
+        cursor_x += glyph.advance;
+    }
+
+    let vertex_count = (vertex_data.len() / 4) as i32;
+
+    let mut vbo = 0;
+    gl::GenBuffers(1, &mut vbo);
+    gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+    if !vertex_data.is_empty() {
+        gl::BufferData(gl::ARRAY_BUFFER, (vertex_data.len() * 4) as isize,
+                        std::mem::transmute(&vertex_data[0]), gl::STATIC_DRAW);
+    }
+
+    Label { vbo, vertex_count, world_position }
+}
+
+/// GLSL source for the label shader pair: transforms the billboard quad's
+/// local offset by the view matrix's rotation only (eye-space offset),
+/// then projects, so labels keep constant screen size and always face the
+/// viewer regardless of head orientation.
+pub const LABEL_VERTEX_SHADER: &str = r#"
+    #version 130
+
+    uniform mat4 view_matrix, proj_matrix;
+    uniform vec3 world_position;
+    in vec2 in_Offset;
+    in vec2 in_TexCoord;
+    varying vec2 tex_coord;
+
+    void main()
+    {
+        tex_coord = in_TexCoord;
+        vec4 eye_position = view_matrix * vec4(world_position, 1.0);
+        gl_Position = proj_matrix * eye_position;
+        // Apply the quad's local offset in clip space, scaled by w, so the
+        // label keeps a constant screen size at any distance/FOV rather
+        // than shrinking with depth like a world/eye-space offset would.
+        gl_Position.xy += in_Offset * gl_Position.w;
+    }
+"#;
+
+pub const LABEL_FRAGMENT_SHADER: &str = r#"
+    #version 130
+
+    uniform sampler2D glyph_texture;
+    varying vec2 tex_coord;
+
+    void main()
+    {
+        vec4 sampled = texture2D(glyph_texture, tex_coord);
+        gl_FragColor = vec4(1.0, 1.0, 1.0, sampled.a);
+    }
+"#;
@@ -6,6 +6,8 @@ use std::fs::File;
 use std::ffi::CString;
 
 mod gl1x;
+mod labels;
+mod mesh;
 
 #[derive(Debug)]
 enum SpecType { O, B, A, F, G, K, M }
@@ -25,9 +27,16 @@ struct Star {
     dec_mins: i8,
     dec_seconds: i8,
     visual_mag: f32,
-    spec_type: SpecType
+    spec_type: SpecType,
+    /// Proper motion in right ascension (already scaled by cos(dec), as
+    /// tabulated) and declination, in milliarcseconds per Julian year.
+    pm_ra_mas_per_year: f32,
+    pm_dec_mas_per_year: f32,
 }
 
+/// Julian date of the J2000.0 epoch that `bsc5.dat` positions are given in.
+const J2000_JD: f64 = 2451545.0;
+
 impl Star {
     fn equatorial_position_as_radians(&self) -> (f32, f32) {
         (f32::consts::PI * 2.0 * ((self.ra_hours as f32 / 24.0f32) +
@@ -38,8 +47,91 @@ impl Star {
                                   (self.dec_seconds as f32 / (90.0*3600.0)))
         )
     }
+
+    /// The star's equatorial position at `jd` (a Julian date), after
+    /// applying proper motion from the J2000.0 catalogue epoch and IAU
+    /// precession of the equinox, in radians.
+    fn equatorial_position_at_epoch_as_radians(&self, jd: f64) -> (f32, f32) {
+        let (ra0, dec0) = self.equatorial_position_as_radians();
+        let years = ((jd - J2000_JD) / 365.25) as f32;
+
+        let mas_to_rad = (f32::consts::PI / 180.0) / 3_600_000.0;
+        // BSC5's pmRA is tabulated as mu_alpha*cos(delta), so dividing back out
+        // by cos(dec) recovers the raw RA rate; guard against the division
+        // blowing up (or going NaN at exactly +/-90 deg) for near-polar stars.
+        let cos_dec = dec0.cos().max(1e-4);
+        let ra = ra0 + self.pm_ra_mas_per_year * mas_to_rad * years / cos_dec;
+        let dec = dec0 + self.pm_dec_mas_per_year * mas_to_rad * years;
+
+        precess_from_j2000(ra, dec, jd)
+    }
+}
+
+/// Rotates a J2000.0 equatorial position to the equinox of date `jd` using
+/// the IAU 1976 (Lieske) precession angles, applied as the usual
+/// zeta/z/theta rotation of the equatorial rectangular coordinates.
+fn precess_from_j2000(ra: f32, dec: f32, jd: f64) -> (f32, f32) {
+    let t = ((jd - J2000_JD) / 36525.0) as f32;
+    let arcsec_to_rad = f32::consts::PI / (180.0 * 3600.0);
+
+    let zeta = (2306.2181 * t + 0.30188 * t * t + 0.017998 * t * t * t) * arcsec_to_rad;
+    let z = (2306.2181 * t + 1.09468 * t * t + 0.018203 * t * t * t) * arcsec_to_rad;
+    let theta = (2004.3109 * t - 0.42665 * t * t - 0.041833 * t * t * t) * arcsec_to_rad;
+
+    let (x0, y0, z0) = (ra.cos() * dec.cos(), ra.sin() * dec.cos(), dec.sin());
+
+    // Standard precession rotation: Rz(-z) * Ry(theta) * Rz(-zeta)
+    let (sz, cz) = zeta.sin_cos();
+    let x1 = cz * x0 - sz * y0;
+    let y1 = sz * x0 + cz * y0;
+    let z1 = z0;
+
+    let (st, ct) = theta.sin_cos();
+    let x2 = ct * x1 - st * z1;
+    let y2 = y1;
+    let z2 = st * x1 + ct * z1;
+
+    let (sz2, cz2) = z.sin_cos();
+    let x3 = cz2 * x2 - sz2 * y2;
+    let y3 = sz2 * x2 + cz2 * y2;
+    let z3 = z2;
+
+    (y3.atan2(x3), z3.asin())
+}
+
+/// Representative effective temperature (Kelvin) for each spectral class,
+/// used as input to the blackbody colour approximation below.
+fn spec_type_temperature(spec_type: &SpecType) -> f32 {
+    match *spec_type {
+        SpecType::O => 30000.0,
+        SpecType::B => 15000.0,
+        SpecType::A => 9000.0,
+        SpecType::F => 6500.0,
+        SpecType::G => 5500.0,
+        SpecType::K => 4300.0,
+        SpecType::M => 3200.0,
+    }
 }
 
+/// Converts a blackbody temperature to a normalized sRGB colour, via
+/// Tanner Helland's closed-form approximation of the Planckian locus.
+/// Shared between shaders via `glazy`'s `#import` preprocessor — the star
+/// shader imports this module directly instead of receiving a
+/// CPU-computed color, so there's a single copy of the conversion.
+const BLACKBODY_GLSL: &str = r#"
+    vec3 kelvin_to_rgb(float kelvin) {
+        float t = kelvin / 100.0;
+
+        float red = t <= 66.0 ? 255.0 : 329.698727446 * pow(t - 60.0, -0.1332047592);
+        float green = t <= 66.0
+            ? 99.4708025861 * log(t) - 161.1195681661
+            : 288.1221695283 * pow(t - 60.0, -0.0755148492);
+        float blue = t >= 66.0 ? 255.0 : (t <= 19.0 ? 0.0 : 138.5177312231 * log(t - 10.0) - 305.0447927307);
+
+        return clamp(vec3(red, green, blue), 0.0, 255.0) / 255.0;
+    }
+"#;
+
 fn load_catalogue() -> Vec<Star> {
     let mut f = File::open("bsc5.dat").expect("bsc5.dat (yale bright star catalogue) not found");
 
@@ -77,7 +169,10 @@ fn load_catalogue() -> Vec<Star> {
                     "W" => SpecType::O,
                     "p" => SpecType::O, /* eta carinae */
                     _ => panic!("Unexpected spectral type in bsc5.dat")
-                }
+                },
+                // proper motion (FK5), tabulated in arcsec/year; stored as mas/year
+                pm_ra_mas_per_year: line.get(148..154).and_then(|s| s.trim().parse::<f32>().ok()).unwrap_or(0.0) * 1000.0,
+                pm_dec_mas_per_year: line.get(154..160).and_then(|s| s.trim().parse::<f32>().ok()).unwrap_or(0.0) * 1000.0,
             })
         } else {
             None
@@ -87,10 +182,105 @@ fn load_catalogue() -> Vec<Star> {
     contents.lines().filter_map(parse_catalogue_line).collect::<Vec<Star>>()
 }
 
+/// Proper names for bright stars and constellations, keyed by `hd_num`,
+/// from an optional side file (one `hd_num<tab>name` pair per line) since
+/// `bsc5.dat` itself carries no human-readable name field.
+fn load_star_names(path: &str) -> std::collections::HashMap<i64, String> {
+    let mut names = std::collections::HashMap::new();
+
+    let contents = match File::open(path).and_then(|mut f| {
+        let mut s = String::new();
+        f.read_to_string(&mut s).map(|_| s)
+    }) {
+        Ok(s) => s,
+        Err(_) => return names,
+    };
+
+    for line in contents.lines() {
+        let mut parts = line.splitn(2, '\t');
+        if let (Some(hd_num), Some(name)) = (parts.next(), parts.next()) {
+            if let Ok(hd_num) = hd_num.parse::<i64>() {
+                names.insert(hd_num, name.to_string());
+            }
+        }
+    }
+
+    names
+}
+
 mod glazy {
     use gl;
     use std;
     use std::ffi::CString;
+    use std::fs::File;
+    use std::io::prelude::*;
+    use std::hash::{Hash, Hasher};
+    use std::collections::{HashMap, HashSet};
+    use std::collections::hash_map::DefaultHasher;
+
+    /// A registry of reusable GLSL snippets, spliced into shader sources at
+    /// `#import "name"` directives before compilation.
+    #[derive(Default)]
+    pub struct ShaderRegistry(HashMap<String, String>);
+
+    impl ShaderRegistry {
+        pub fn new() -> ShaderRegistry {
+            ShaderRegistry(HashMap::new())
+        }
+
+        pub fn register(&mut self, name: &str, src: &str) {
+            self.0.insert(name.to_string(), src.to_string());
+        }
+
+        /// Resolves every `#import "name"` directive in `src`, recursively
+        /// splicing in the registered module, deduplicating modules that
+        /// are imported more than once, and rewriting `#line` directives so
+        /// compiler errors still point at the importing source.
+        fn resolve(&self, src: &str, already_included: &mut HashSet<String>, include_stack: &mut Vec<String>) -> String {
+            let mut out = String::new();
+            let mut line_no = 1;
+
+            for line in src.lines() {
+                let trimmed = line.trim();
+                if let Some(name) = parse_import(trimmed) {
+                    if include_stack.contains(&name) {
+                        panic!("cyclic #import of \"{}\" (via {:?})", name, include_stack);
+                    }
+                    if !already_included.contains(&name) {
+                        already_included.insert(name.clone());
+                        let module_src = self.0.get(&name)
+                            .unwrap_or_else(|| panic!("#import \"{}\" not found in shader registry", name));
+
+                        include_stack.push(name.clone());
+                        out.push_str(&self.resolve(module_src, already_included, include_stack));
+                        include_stack.pop();
+
+                        out.push_str(&format!("#line {}\n", line_no + 1));
+                    }
+                } else {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+                line_no += 1;
+            }
+
+            out
+        }
+
+        pub fn resolve_imports(&self, src: &str) -> String {
+            self.resolve(src, &mut HashSet::new(), &mut Vec::new())
+        }
+    }
+
+    fn parse_import(line: &str) -> Option<String> {
+        if !line.starts_with("#import") {
+            return None;
+        }
+        let rest = line["#import".len()..].trim();
+        let rest = rest.strip_prefix('"')?;
+        let end = rest.find('"')?;
+        Some(rest[..end].to_string())
+    }
 
     pub struct Shader(u32);
 
@@ -100,7 +290,10 @@ mod glazy {
             id
         }
 
-        pub unsafe fn new(vert_src: &str, frag_src: &str) -> Shader {
+        pub unsafe fn new(vert_src: &str, frag_src: &str, registry: &ShaderRegistry) -> Shader {
+            let vert_src = registry.resolve_imports(vert_src);
+            let frag_src = registry.resolve_imports(frag_src);
+
             let fragment_shader = gl::CreateShader(gl::FRAGMENT_SHADER);
             let vertex_shader = gl::CreateShader(gl::VERTEX_SHADER);
             gl::ShaderSource(vertex_shader, 1, &CString::new(vert_src).unwrap().as_ptr(), std::ptr::null());
@@ -115,12 +308,120 @@ mod glazy {
             Shader(shader_program)
         }
 
+        /// Like `new`, but tries to skip source compilation by loading a
+        /// previously linked program binary from `cache_dir`, keyed by a
+        /// hash of the combined source and the driver's vendor/renderer
+        /// string (binaries aren't portable across drivers). Falls back to
+        /// a full `new` + recompile if there's no cache entry, or the
+        /// driver rejects the cached binary.
+        pub unsafe fn new_cached(vert_src: &str, frag_src: &str, registry: &ShaderRegistry, cache_dir: &str) -> Shader {
+            let _ = std::fs::create_dir_all(cache_dir);
+            let cache_path = format!("{}/{:016x}.binshader", cache_dir, cache_key(vert_src, frag_src));
+
+            if let Some(shader) = Self::load_from_cache(&cache_path) {
+                return shader;
+            }
+
+            let shader = Self::new(vert_src, frag_src, registry);
+            shader.write_to_cache(&cache_path);
+            shader
+        }
+
+        unsafe fn load_from_cache(cache_path: &str) -> Option<Shader> {
+            let mut f = File::open(cache_path).ok()?;
+            let mut data = Vec::new();
+            f.read_to_end(&mut data).ok()?;
+            if data.len() <= 4 {
+                return None;
+            }
+
+            let format = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+            let binary = &data[4..];
+
+            let shader_program = gl::CreateProgram();
+            gl::ProgramBinary(shader_program, format, std::mem::transmute(&binary[0]), binary.len() as i32);
+
+            let mut link_status = 0;
+            gl::GetProgramiv(shader_program, gl::LINK_STATUS, &mut link_status);
+            if link_status == 0 {
+                gl::DeleteProgram(shader_program);
+                return None;
+            }
+
+            Some(Shader(shader_program))
+        }
+
+        unsafe fn write_to_cache(&self, cache_path: &str) {
+            let mut binary_length = 0;
+            gl::GetProgramiv(self.id(), gl::PROGRAM_BINARY_LENGTH, &mut binary_length);
+            if binary_length <= 0 {
+                return;
+            }
+
+            let mut binary = vec![0u8; binary_length as usize];
+            let mut format = 0u32;
+            let mut written_length = 0;
+            gl::GetProgramBinary(self.id(), binary_length, &mut written_length,
+                                  &mut format, std::mem::transmute(&mut binary[0]));
+            binary.truncate(written_length as usize);
+
+            if let Ok(mut f) = File::create(cache_path) {
+                let _ = f.write_all(&format.to_le_bytes());
+                let _ = f.write_all(&binary);
+            }
+        }
+
         pub unsafe fn getUniformLocation(&self, name: &str) -> i32 {
             gl::GetUniformLocation(self.id(), CString::new(name).unwrap().as_ptr())
         }
     }
+
+    unsafe fn gl_string(name: u32) -> String {
+        let ptr = gl::GetString(name);
+        if ptr.is_null() {
+            return String::new();
+        }
+        std::ffi::CStr::from_ptr(ptr as *const i8).to_string_lossy().into_owned()
+    }
+
+    unsafe fn cache_key(vert_src: &str, frag_src: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        vert_src.hash(&mut hasher);
+        frag_src.hash(&mut hasher);
+        gl_string(gl::VENDOR).hash(&mut hasher);
+        gl_string(gl::RENDERER).hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Builds the interleaved `position, (kelvin, brightness)` vertex data for
+/// the star point cloud, with each star's position precessed and
+/// proper-motioned to `jd` (a Julian date). The blackbody temperature is
+/// passed through as-is and turned into a color by the star shader's
+/// imported `kelvin_to_rgb`, rather than duplicating that conversion here.
+fn build_star_vertex_data(catalogue: &[Star], jd: f64) -> Vec<f32> {
+    let mut star_data: Vec<f32> = Vec::new();
+
+    for s in catalogue {
+        let (ra, dec) = s.equatorial_position_at_epoch_as_radians(jd);
+
+        star_data.push(-10.0 * ra.sin() * dec.cos());
+        star_data.push( 10.0 * dec.sin());
+        star_data.push(-10.0 * ra.cos() * dec.cos());
+
+        let brightness = ((6.0 - s.visual_mag)/7.0).max(0.0);
+
+        star_data.push(spec_type_temperature(&s.spec_type));
+        star_data.push(brightness);
+    }
+
+    star_data
 }
 
+/// Simulated days advanced per real-world second; drives how fast
+/// precession and proper motion visibly drift the star field.
+const TIME_ACCELERATION_DAYS_PER_SECOND: f64 = 1.0;
+
 fn main() {
     let context = osvr::Context::new("Rust OSVR example");
     let mut render = osvr::RenderManager::new(&context).unwrap();
@@ -136,72 +437,54 @@ fn main() {
 
     let catalogue = load_catalogue();
 
-    let (star_vbo, shader_program, proj_matrix, view_matrix) = {
-
-        let mut star_data: Vec<f32> = Vec::new();
-
-        for s in &catalogue {
-            let (ra, dec) = s.equatorial_position_as_radians();
+    let mut shader_registry = glazy::ShaderRegistry::new();
+    shader_registry.register("blackbody", BLACKBODY_GLSL);
 
-            star_data.push(-10.0 * ra.sin() * dec.cos());
-            star_data.push( 10.0 * dec.sin());
-            star_data.push(-10.0 * ra.cos() * dec.cos());
-
-            let a = ((6.0 - s.visual_mag)/7.0).max(0.0);
+    let (star_vbo, shader_program, proj_matrix, view_matrix) = {
 
-            for color_component in match s.spec_type {
-                // [r, g, b]
-                SpecType::O => [a, a, a],
-                SpecType::B => [a, a, a],
-                SpecType::A => [a, a, a],
-                SpecType::F => [a, a, a],
-                SpecType::G => [a, a, a*0.9],
-                SpecType::K => [a, a*0.9, a*0.75],
-                SpecType::M => [a, a*0.75, a*0.5],
-            }.iter() {
-                star_data.push(*color_component)
-            }
-        }
+        let star_data = build_star_vertex_data(&catalogue, J2000_JD);
 
         unsafe {
             let mut star_vbo = 0;
 
             gl::GenBuffers(1, &mut star_vbo);
             gl::BindBuffer(gl::ARRAY_BUFFER, star_vbo);
-            gl::BufferData(gl::ARRAY_BUFFER, (star_data.len()*4) as isize, std::mem::transmute(&star_data[0]), gl::STATIC_DRAW);
+            gl::BufferData(gl::ARRAY_BUFFER, (star_data.len()*4) as isize, std::mem::transmute(&star_data[0]), gl::DYNAMIC_DRAW);
             gl::BindBuffer(gl::ARRAY_BUFFER, star_vbo);
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 6*4, std::mem::transmute(0u64));
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 5*4, std::mem::transmute(0u64));
             gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, 6*4, std::mem::transmute(12u64));
+            gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, 5*4, std::mem::transmute(12u64));
             gl::EnableVertexAttribArray(1);
             gl::BindBuffer(gl::ARRAY_BUFFER, star_vbo);
 
-            let shader = glazy::Shader::new(
+            let shader = glazy::Shader::new_cached(
                 r#"
                 #version 130 // Specify which version of GLSL we are using.
+                #import "blackbody"
 
                 uniform mat4 view_matrix, proj_matrix;
                 in vec3 in_Position;
-                in vec3 in_Color;
+                in vec2 in_StarParams; // (blackbody temperature in kelvin, brightness)
                 varying vec4 color;
 
-                void main() 
+                void main()
                 {
-                    gl_PointSize = max(1.0, in_Color.r * 3.0);
-                    color = vec4(in_Color.r, in_Color.g, in_Color.b, 1.0);
+                    float brightness = in_StarParams.y;
+                    gl_PointSize = max(1.0, brightness * 3.0);
+                    color = vec4(kelvin_to_rgb(in_StarParams.x) * brightness, 1.0);
                     gl_Position = proj_matrix * view_matrix * vec4(in_Position.x, in_Position.y, in_Position.z, 1.0);
                 }
             "#, r#"
                 #version 130 // Specify which version of GLSL we are using.
                 varying vec4 color;
 
-                void main() 
+                void main()
                 {
                     gl_FragColor = color;
                 }
-            "#);
+            "#, &shader_registry, "shader_cache");
             gl::BindAttribLocation(shader.id(), 0, CString::new("in_Position").unwrap().as_ptr());
-            gl::BindAttribLocation(shader.id(), 1, CString::new("in_Color").unwrap().as_ptr());
+            gl::BindAttribLocation(shader.id(), 1, CString::new("in_StarParams").unwrap().as_ptr());
             let proj_matrix = shader.getUniformLocation("proj_matrix");
             let view_matrix = shader.getUniformLocation("view_matrix");
 
@@ -212,8 +495,79 @@ fn main() {
         }
     };
 
+    let star_names = load_star_names("star_names.dat");
+
+    // skipped entirely (no labels drawn) if the font atlas assets aren't present
+    let mut label_state: Option<(u32, i32, i32, i32, u32, Vec<(usize, labels::Label)>)> = unsafe {
+        labels::load_font_atlas("font_atlas.json", "font_atlas.rgba").map(|font_atlas| {
+            let shader = glazy::Shader::new(labels::LABEL_VERTEX_SHADER, labels::LABEL_FRAGMENT_SHADER, &shader_registry);
+            gl::BindAttribLocation(shader.id(), 0, CString::new("in_Offset").unwrap().as_ptr());
+            gl::BindAttribLocation(shader.id(), 1, CString::new("in_TexCoord").unwrap().as_ptr());
+            let proj_matrix = shader.getUniformLocation("proj_matrix");
+            let view_matrix = shader.getUniformLocation("view_matrix");
+            let world_position = shader.getUniformLocation("world_position");
+
+            // keep each label paired with its star's catalogue index so its
+            // world_position can be re-derived as the star precesses/drifts
+            let star_labels = catalogue.iter()
+                .enumerate()
+                .filter_map(|(i, s)| star_names.get(&s.hd_num).map(|name| (i, s, name)))
+                .map(|(i, s, name)| {
+                    let (ra, dec) = s.equatorial_position_as_radians();
+                    let world_position = (-10.0 * ra.sin() * dec.cos(), 10.0 * dec.sin(), -10.0 * ra.cos() * dec.cos());
+                    (i, labels::build_label(&font_atlas, name, world_position, 0.01))
+                })
+                .collect::<Vec<(usize, labels::Label)>>();
+
+            (shader.id(), proj_matrix, view_matrix, world_position, font_atlas.texture, star_labels)
+        })
+    };
+
+    // meshes whose .obj asset isn't present are skipped, not fatal
+    let (mesh_shader, mesh_proj_matrix, mesh_view_matrix, mesh_world_matrix, meshes) = unsafe {
+        let shader = glazy::Shader::new(mesh::MESH_VERTEX_SHADER, mesh::MESH_FRAGMENT_SHADER, &shader_registry);
+        gl::BindAttribLocation(shader.id(), 0, CString::new("in_Position").unwrap().as_ptr());
+        gl::BindAttribLocation(shader.id(), 1, CString::new("in_Normal").unwrap().as_ptr());
+        gl::BindAttribLocation(shader.id(), 2, CString::new("in_UV").unwrap().as_ptr());
+        let proj_matrix = shader.getUniformLocation("proj_matrix");
+        let view_matrix = shader.getUniformLocation("view_matrix");
+        let world_matrix = shader.getUniformLocation("world_matrix");
+
+        let meshes = match mesh::Mesh::load("horizon.obj") {
+            Some(mut horizon) => {
+                horizon.place_at(0.0, -f32::consts::FRAC_PI_2, 10.0, 10.0);
+                vec![horizon]
+            }
+            None => Vec::new(),
+        };
+
+        (shader.id(), proj_matrix, view_matrix, world_matrix, meshes)
+    };
+
+    let sim_clock = std::time::Instant::now();
+    let mut last_rebuilt_jd_floor = J2000_JD.floor() as i64;
+
     loop {
         context.update();
+
+        let simulated_jd = J2000_JD + sim_clock.elapsed().as_secs_f64() * TIME_ACCELERATION_DAYS_PER_SECOND;
+        if simulated_jd.floor() as i64 != last_rebuilt_jd_floor {
+            last_rebuilt_jd_floor = simulated_jd.floor() as i64;
+            let star_data = build_star_vertex_data(&catalogue, simulated_jd);
+            unsafe {
+                gl::BindBuffer(gl::ARRAY_BUFFER, star_vbo);
+                gl::BufferData(gl::ARRAY_BUFFER, (star_data.len()*4) as isize,
+                                std::mem::transmute(&star_data[0]), gl::DYNAMIC_DRAW);
+            }
+
+            if let Some((_, _, _, _, _, labels)) = &mut label_state {
+                for (catalogue_index, label) in labels {
+                    let (ra, dec) = catalogue[*catalogue_index].equatorial_position_at_epoch_as_radians(simulated_jd);
+                    label.world_position = (-10.0 * ra.sin() * dec.cos(), 10.0 * dec.sin(), -10.0 * ra.cos() * dec.cos());
+                }
+            }
+        }
+
         render.render_eyes(|render_info, frame_buffer, color_buffer, depth_buffer| {
             osvr::glutil::bind_buffers(frame_buffer, color_buffer, depth_buffer);
             osvr::glutil::set_viewport(render_info);
@@ -233,13 +587,66 @@ fn main() {
                 gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
                 // draw stars
+                // (no VAOs here, so attrib pointers *and* blend/depth state are global,
+                // shared with the label/mesh passes below — re-specify ours every frame
+                // rather than relying on whatever the previous frame's last pass left set)
+                gl::Disable(gl::DEPTH_TEST);
+                gl::Enable(gl::BLEND);
+                gl::BlendFunc(gl::ONE, gl::ONE);
+                gl::BindBuffer(gl::ARRAY_BUFFER, star_vbo);
+                gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 5*4, std::mem::transmute(0u64));
                 gl::EnableVertexAttribArray(0);
+                gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, 5*4, std::mem::transmute(12u64));
                 gl::EnableVertexAttribArray(1);
-                gl::BindBuffer(gl::ARRAY_BUFFER, star_vbo);
                 gl::UseProgram(shader_program);
                 gl::UniformMatrix4fv(proj_matrix, 1, 0, &_projection[0]);
                 gl::UniformMatrix4fv(view_matrix, 1, 0, &_modelview[0]);
                 gl::DrawArrays(gl1x::POINTS, 0, catalogue.len() as i32);
+
+                // draw star/constellation name labels, billboarded to face the viewer
+                // (alpha blending, so only the glyph shape shows and not the whole quad)
+                if let Some((label_shader, label_proj_matrix, label_view_matrix, label_world_position, label_texture, labels)) = &label_state {
+                    gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+                    gl::UseProgram(*label_shader);
+                    gl::UniformMatrix4fv(*label_proj_matrix, 1, 0, &_projection[0]);
+                    gl::UniformMatrix4fv(*label_view_matrix, 1, 0, &_modelview[0]);
+                    gl::ActiveTexture(gl::TEXTURE0);
+                    gl::BindTexture(gl::TEXTURE_2D, *label_texture);
+
+                    for (_, label) in labels {
+                        gl::Uniform3f(*label_world_position, label.world_position.0, label.world_position.1, label.world_position.2);
+                        gl::BindBuffer(gl::ARRAY_BUFFER, label.vbo());
+                        gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 4*4, std::mem::transmute(0u64));
+                        gl::EnableVertexAttribArray(0);
+                        gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, 4*4, std::mem::transmute(8u64));
+                        gl::EnableVertexAttribArray(1);
+                        gl::DrawArrays(gl::TRIANGLES, 0, label.vertex_count());
+                    }
+                }
+
+                // draw meshes (planets, horizon, ...)
+                // (solid bodies: depth-test so they occlude stars/each other, and
+                // opaque blending so back faces don't add through the front ones)
+                gl::Enable(gl::DEPTH_TEST);
+                gl::Disable(gl::BLEND);
+                gl::UseProgram(mesh_shader);
+                gl::UniformMatrix4fv(mesh_proj_matrix, 1, 0, &_projection[0]);
+                gl::UniformMatrix4fv(mesh_view_matrix, 1, 0, &_modelview[0]);
+
+                for m in &meshes {
+                    gl::UniformMatrix4fv(mesh_world_matrix, 1, 0, &m.transform[0]);
+                    gl::ActiveTexture(gl::TEXTURE0);
+                    gl::BindTexture(gl::TEXTURE_2D, m.texture());
+                    gl::BindBuffer(gl::ARRAY_BUFFER, m.vbo());
+                    gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, m.ibo());
+                    gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 8*4, std::mem::transmute(0u64));
+                    gl::EnableVertexAttribArray(0);
+                    gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, 8*4, std::mem::transmute(12u64));
+                    gl::EnableVertexAttribArray(1);
+                    gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, 8*4, std::mem::transmute(24u64));
+                    gl::EnableVertexAttribArray(2);
+                    gl::DrawElements(gl::TRIANGLES, m.index_count(), gl::UNSIGNED_INT, std::mem::transmute(0u64));
+                }
             }
         });
     }
@@ -0,0 +1,223 @@
+use gl;
+use std;
+use std::fs::File;
+use std::io::prelude::*;
+
+/// A loaded Wavefront OBJ mesh: an interleaved `position, normal, uv` VBO
+/// plus an index buffer, ready to be drawn with `gl::DrawElements`.
+pub struct Mesh {
+    vbo: u32,
+    ibo: u32,
+    index_count: i32,
+    texture: u32,
+    pub transform: [f32; 16],
+}
+
+impl Mesh {
+    pub fn vbo(&self) -> u32 {
+        self.vbo
+    }
+
+    pub fn ibo(&self) -> u32 {
+        self.ibo
+    }
+
+    pub fn index_count(&self) -> i32 {
+        self.index_count
+    }
+
+    pub fn texture(&self) -> u32 {
+        self.texture
+    }
+
+    /// Replaces the mesh's texture (a flat white placeholder by default)
+    /// with a raw RGBA file of the given dimensions, uploaded as a GL
+    /// texture, so a real sphere/ground texture can be applied.
+    pub unsafe fn set_texture_rgba(&mut self, texture_rgba_path: &str, width: i32, height: i32) {
+        self.texture = load_texture_rgba(texture_rgba_path, width, height);
+    }
+
+    /// Parses `path` as a Wavefront OBJ (`v`, `vt`, `vn`, and triangulated
+    /// `f vertex/uv/normal` faces), builds an interleaved VBO of
+    /// `(position, normal, uv)` and an index buffer, and uploads both. The
+    /// mesh starts with an identity world transform; use `place_at` to
+    /// position it. Returns `None` if `path` can't be opened or read, so a
+    /// missing mesh asset just skips that mesh rather than crashing.
+    pub unsafe fn load(path: &str) -> Option<Mesh> {
+        let contents = match File::open(path).and_then(|mut f| {
+            let mut s = String::new();
+            f.read_to_string(&mut s).map(|_| s)
+        }) {
+            Ok(s) => s,
+            Err(_) => return None,
+        };
+
+        let mut positions: Vec<[f32; 3]> = Vec::new();
+        let mut uvs: Vec<[f32; 2]> = Vec::new();
+        let mut normals: Vec<[f32; 3]> = Vec::new();
+
+        let mut vertices: Vec<f32> = Vec::new();
+        let mut indices: Vec<u32> = Vec::new();
+        let mut vertex_cache: std::collections::HashMap<(usize, usize, usize), u32> = std::collections::HashMap::new();
+
+        for line in contents.lines() {
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("v") => positions.push(parse_vec3(tokens)),
+                Some("vt") => uvs.push(parse_vec2(tokens)),
+                Some("vn") => normals.push(parse_vec3(tokens)),
+                Some("f") => {
+                    for vertex_ref in tokens {
+                        let key = parse_face_vertex(vertex_ref);
+                        let index = *vertex_cache.entry(key).or_insert_with(|| {
+                            let (pos_i, uv_i, norm_i) = key;
+                            let position = positions[pos_i - 1];
+                            let normal = if norm_i > 0 { normals[norm_i - 1] } else { [0.0, 0.0, 0.0] };
+                            let uv = if uv_i > 0 { uvs[uv_i - 1] } else { [0.0, 0.0] };
+
+                            vertices.extend_from_slice(&position);
+                            vertices.extend_from_slice(&normal);
+                            vertices.extend_from_slice(&uv);
+
+                            ((vertices.len() / 8) - 1) as u32
+                        });
+                        indices.push(index);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut vbo = 0;
+        gl::GenBuffers(1, &mut vbo);
+        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+        gl::BufferData(gl::ARRAY_BUFFER, (vertices.len() * 4) as isize,
+                        std::mem::transmute(&vertices[0]), gl::STATIC_DRAW);
+
+        let mut ibo = 0;
+        gl::GenBuffers(1, &mut ibo);
+        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ibo);
+        gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, (indices.len() * 4) as isize,
+                        std::mem::transmute(&indices[0]), gl::STATIC_DRAW);
+
+        Some(Mesh { vbo, ibo, index_count: indices.len() as i32, texture: white_texture(), transform: identity_matrix() })
+    }
+
+    /// Positions the mesh at the given right ascension/declination (in
+    /// radians, via the same convention as `Star::equatorial_position_as_radians`),
+    /// at `distance` from the origin, uniformly scaled by `scale`.
+    pub fn place_at(&mut self, ra: f32, dec: f32, distance: f32, scale: f32) {
+        let x = -distance * ra.sin() * dec.cos();
+        let y = distance * dec.sin();
+        let z = -distance * ra.cos() * dec.cos();
+
+        self.transform = [
+            scale, 0.0, 0.0, 0.0,
+            0.0, scale, 0.0, 0.0,
+            0.0, 0.0, scale, 0.0,
+            x, y, z, 1.0,
+        ];
+    }
+}
+
+/// A 1x1 opaque white texture, so a mesh with no real texture assigned
+/// still renders its Lambertian shading instead of sampling whatever
+/// texture unit 0 happens to hold from an earlier draw pass.
+unsafe fn white_texture() -> u32 {
+    let white_pixel: [u8; 4] = [255, 255, 255, 255];
+    upload_rgba_texture(&white_pixel, 1, 1)
+}
+
+/// Reads a raw (undecoded) RGBA byte file of the given dimensions and
+/// uploads it as a GL texture.
+unsafe fn load_texture_rgba(path: &str, width: i32, height: i32) -> u32 {
+    let mut data = Vec::new();
+    File::open(path)
+        .expect("mesh texture not found")
+        .read_to_end(&mut data)
+        .expect("could not read mesh texture");
+    upload_rgba_texture(&data, width, height)
+}
+
+unsafe fn upload_rgba_texture(rgba: &[u8], width: i32, height: i32) -> u32 {
+    let mut texture = 0;
+    gl::GenTextures(1, &mut texture);
+    gl::BindTexture(gl::TEXTURE_2D, texture);
+    gl::TexImage2D(
+        gl::TEXTURE_2D, 0, gl::RGBA as i32, width, height, 0,
+        gl::RGBA, gl::UNSIGNED_BYTE, std::mem::transmute(&rgba[0]));
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+    texture
+}
+
+fn identity_matrix() -> [f32; 16] {
+    [
+        1.0, 0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    ]
+}
+
+fn parse_vec3(mut tokens: std::str::SplitWhitespace) -> [f32; 3] {
+    [
+        tokens.next().and_then(|t| t.parse().ok()).unwrap_or(0.0),
+        tokens.next().and_then(|t| t.parse().ok()).unwrap_or(0.0),
+        tokens.next().and_then(|t| t.parse().ok()).unwrap_or(0.0),
+    ]
+}
+
+fn parse_vec2(mut tokens: std::str::SplitWhitespace) -> [f32; 2] {
+    [
+        tokens.next().and_then(|t| t.parse().ok()).unwrap_or(0.0),
+        tokens.next().and_then(|t| t.parse().ok()).unwrap_or(0.0),
+    ]
+}
+
+/// Parses an OBJ face vertex reference of the form `vertex/uv/normal`
+/// (uv and normal are optional) into 1-based indices, with 0 meaning
+/// "not present".
+fn parse_face_vertex(vertex_ref: &str) -> (usize, usize, usize) {
+    let mut parts = vertex_ref.split('/');
+    let pos = parts.next().unwrap_or("0").parse::<usize>().unwrap_or(0);
+    let uv = parts.next().unwrap_or("0").parse::<usize>().unwrap_or(0);
+    let norm = parts.next().unwrap_or("0").parse::<usize>().unwrap_or(0);
+    (pos, uv, norm)
+}
+
+/// GLSL source for the lit mesh shader: transforms positions by a
+/// per-mesh world transform before the usual view/projection, and shades
+/// with simple Lambertian lighting from a fixed directional light.
+pub const MESH_VERTEX_SHADER: &str = r#"
+    #version 130
+
+    uniform mat4 view_matrix, proj_matrix, world_matrix;
+    in vec3 in_Position;
+    in vec3 in_Normal;
+    in vec2 in_UV;
+    varying vec3 normal;
+    varying vec2 uv;
+
+    void main()
+    {
+        normal = mat3(world_matrix) * in_Normal;
+        uv = in_UV;
+        gl_Position = proj_matrix * view_matrix * world_matrix * vec4(in_Position, 1.0);
+    }
+"#;
+
+pub const MESH_FRAGMENT_SHADER: &str = r#"
+    #version 130
+
+    uniform sampler2D mesh_texture;
+    varying vec3 normal;
+    varying vec2 uv;
+
+    void main()
+    {
+        vec3 light_direction = normalize(vec3(0.3, 0.6, 0.7));
+        float diffuse = max(dot(normalize(normal), light_direction), 0.1);
+        gl_FragColor = vec4(texture2D(mesh_texture, uv).rgb * diffuse, 1.0);
+    }
+"#;